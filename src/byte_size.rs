@@ -0,0 +1,18 @@
+const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+
+/// Formats a byte count as a human-readable string (`"12.34 MB"`), used
+/// when reporting the size of exported files on disk.
+pub fn human_bytes(bytes: u64) -> String {
+	if bytes < 1024 {
+		return format!("{} {}", bytes, UNITS[0]);
+	}
+
+	let mut size = bytes as f64;
+	let mut unit_index = 0;
+	while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+		size /= 1024.0;
+		unit_index += 1;
+	}
+
+	format!("{:.2} {}", size, UNITS[unit_index])
+}
@@ -0,0 +1,93 @@
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn default_num_runs() -> usize { 3 }
+fn default_min_peers() -> u32 { 75 }
+fn default_chain() -> String { String::from("foundation") }
+fn default_data_collection_duration_secs() -> u64 { 60 * 10 }
+fn default_data_collection_interval_ms() -> u64 { 500 }
+fn default_analysis_time_skip_secs() -> u64 { 60 * 5 }
+
+/// Run parameters and node flags, loaded from an optional TOML file and
+/// merged with any CLI overrides in `main`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	pub num_runs: usize,
+	pub min_peers: u32,
+	pub chain: String,
+	pub data_collection_duration_secs: u64,
+	pub data_collection_interval_ms: u64,
+	pub analysis_time_skip_secs: u64,
+	/// Extra CLI flags appended as-is to the spawned node command, so
+	/// other chains or binaries can be targeted without recompiling.
+	pub extra_args: Vec<String>,
+	/// Show a full-screen live dashboard instead of a progress bar
+	/// while `Runner::collect_data` is sampling.
+	pub tui: bool,
+	/// Forces a specific `NodeAdapter` (`"parity"` or `"geth"`) instead of
+	/// auto-detecting it from the binary's `--version` output.
+	pub client: Option<String>,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			num_runs: default_num_runs(),
+			min_peers: default_min_peers(),
+			chain: default_chain(),
+			data_collection_duration_secs: default_data_collection_duration_secs(),
+			data_collection_interval_ms: default_data_collection_interval_ms(),
+			analysis_time_skip_secs: default_analysis_time_skip_secs(),
+			extra_args: Vec::new(),
+			tui: false,
+			client: None,
+		}
+	}
+}
+
+impl Config {
+	/// Load the config from `path`, or from the XDG-style default location
+	/// when no path is given, using hardcoded defaults if that file does not
+	/// exist. A given `path` that does not exist also yields the defaults;
+	/// the XDG location is not tried in that case.
+	pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+		let path = match path {
+			Some(path) => Some(path.to_path_buf()),
+			None => Config::default_path(),
+		};
+
+		let path = match path {
+			Some(ref path) if path.is_file() => path.clone(),
+			_ => return Ok(Config::default()),
+		};
+
+		let contents = fs::read_to_string(&path)?;
+		toml::from_str(&contents)
+			.map_err(|e| Error::new(ErrorKind::Other, format!("Could not parse config file {}: {}", path.display(), e)))
+	}
+
+	/// `$XDG_CONFIG_HOME/eth-metrics/config.toml`, falling back to
+	/// `~/.config/eth-metrics/config.toml`.
+	fn default_path() -> Option<PathBuf> {
+		let config_home = env::var("XDG_CONFIG_HOME").ok().map(PathBuf::from)
+			.or_else(|| env::var("HOME").ok().map(|home| PathBuf::from(home).join(".config")))?;
+
+		Some(config_home.join("eth-metrics").join("config.toml"))
+	}
+
+	pub fn data_collection_duration(&self) -> Duration {
+		Duration::from_secs(self.data_collection_duration_secs)
+	}
+
+	pub fn data_collection_interval(&self) -> Duration {
+		Duration::from_millis(self.data_collection_interval_ms)
+	}
+
+	pub fn analysis_time_skip(&self) -> Duration {
+		Duration::from_secs(self.analysis_time_skip_secs)
+	}
+}
@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+use std::io::{self, Error, Stdout};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use tui::backend::CrosstermBackend;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Color, Style};
+use tui::symbols;
+use tui::text::Span;
+use tui::widgets::{Axis, Block, Borders, Chart, Dataset, Gauge, Sparkline};
+use tui::Terminal;
+
+/// How many samples are kept on screen at once.
+const HISTORY_LEN: usize = 200;
+
+/// A full-screen live dashboard shown while `Runner::collect_data` is
+/// sampling, gated behind the `--tui` flag.
+pub struct Dashboard {
+	terminal: Terminal<CrosstermBackend<Stdout>>,
+	min_peers: u32,
+	block_heights: VecDeque<(f64, f64)>,
+	block_speeds: VecDeque<(f64, f64)>,
+	peer_counts: VecDeque<u64>,
+}
+
+impl Dashboard {
+	pub fn new(min_peers: u32) -> Result<Self, Error> {
+		enable_raw_mode()?;
+		let mut stdout = io::stdout();
+		execute!(stdout, EnterAlternateScreen)?;
+
+		let backend = CrosstermBackend::new(stdout);
+		let terminal = Terminal::new(backend)?;
+
+		Ok(Dashboard {
+			terminal,
+			min_peers,
+			block_heights: VecDeque::with_capacity(HISTORY_LEN),
+			block_speeds: VecDeque::with_capacity(HISTORY_LEN),
+			peer_counts: VecDeque::with_capacity(HISTORY_LEN),
+		})
+	}
+
+	/// Feed a new sample into the ring buffers, dropping the oldest one
+	/// once `HISTORY_LEN` is reached.
+	pub fn push(&mut self, time: f64, block_height: f64, block_speed: f64, peer_count: u64) {
+		push_bounded(&mut self.block_heights, (time, block_height));
+		push_bounded(&mut self.block_speeds, (time, block_speed));
+		push_bounded(&mut self.peer_counts, peer_count);
+	}
+
+	/// Returns `true` if the user asked to abort (`q` or Ctrl-C).
+	pub fn should_quit(&self) -> Result<bool, Error> {
+		if !event::poll(Duration::from_millis(0))? {
+			return Ok(false);
+		}
+
+		match event::read()? {
+			Event::Key(key) => Ok(
+				key.code == KeyCode::Char('q') ||
+				(key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+			),
+			_ => Ok(false),
+		}
+	}
+
+	pub fn draw(&mut self) -> Result<(), Error> {
+		let min_peers = self.min_peers;
+		let block_heights: Vec<(f64, f64)> = self.block_heights.iter().cloned().collect();
+		let block_speeds: Vec<(f64, f64)> = self.block_speeds.iter().cloned().collect();
+		let peer_counts: Vec<u64> = self.peer_counts.iter().cloned().collect();
+		let current_peers = peer_counts.last().cloned().unwrap_or(0);
+
+		self.terminal.draw(|f| {
+			let chunks = Layout::default()
+				.direction(Direction::Vertical)
+				.constraints([Constraint::Percentage(40), Constraint::Percentage(40), Constraint::Length(3)].as_ref())
+				.split(f.size());
+
+			let height_bounds = axis_bounds(&block_heights);
+			let height_dataset = Dataset::default()
+				.name("Block height")
+				.marker(symbols::Marker::Braille)
+				.style(Style::default().fg(Color::Cyan))
+				.data(&block_heights);
+			let height_chart = Chart::new(vec![height_dataset])
+				.block(Block::default().title(Span::raw("Block height")).borders(Borders::ALL))
+				.x_axis(Axis::default().bounds([block_heights.first().map_or(0.0, |p| p.0), block_heights.last().map_or(1.0, |p| p.0)]))
+				.y_axis(Axis::default().bounds(height_bounds));
+			f.render_widget(height_chart, chunks[0]);
+
+			let speed_bounds = axis_bounds(&block_speeds);
+			let speed_dataset = Dataset::default()
+				.name("Block speed (bps)")
+				.marker(symbols::Marker::Braille)
+				.style(Style::default().fg(Color::Green))
+				.data(&block_speeds);
+			let speed_chart = Chart::new(vec![speed_dataset])
+				.block(Block::default().title(Span::raw("Block speed")).borders(Borders::ALL))
+				.x_axis(Axis::default().bounds([block_speeds.first().map_or(0.0, |p| p.0), block_speeds.last().map_or(1.0, |p| p.0)]))
+				.y_axis(Axis::default().bounds(speed_bounds));
+			f.render_widget(speed_chart, chunks[1]);
+
+			let peers_row = Layout::default()
+				.direction(Direction::Horizontal)
+				.constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+				.split(chunks[2]);
+
+			let sparkline_data: Vec<u64> = peer_counts.clone();
+			let sparkline = Sparkline::default()
+				.block(Block::default().title(Span::raw("Peers")).borders(Borders::ALL))
+				.data(&sparkline_data)
+				.style(Style::default().fg(Color::Yellow));
+			f.render_widget(sparkline, peers_row[0]);
+
+			let ratio = if min_peers == 0 { 1.0 } else { (current_peers as f64 / min_peers as f64).min(1.0) };
+			let gauge = Gauge::default()
+				.block(Block::default().title(Span::raw("Peers / min-peers")).borders(Borders::ALL))
+				.gauge_style(Style::default().fg(Color::Magenta))
+				.ratio(ratio)
+				.label(format!("{}/{}", current_peers, min_peers));
+			f.render_widget(gauge, peers_row[1]);
+		})?;
+
+		Ok(())
+	}
+}
+
+impl Drop for Dashboard {
+	fn drop(&mut self) {
+		let _ = disable_raw_mode();
+		let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+	}
+}
+
+fn push_bounded<T>(buffer: &mut VecDeque<T>, value: T) {
+	if buffer.len() == HISTORY_LEN {
+		buffer.pop_front();
+	}
+	buffer.push_back(value);
+}
+
+fn axis_bounds(points: &[(f64, f64)]) -> [f64; 2] {
+	let min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+	let max = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+	if !min.is_finite() || !max.is_finite() {
+		[0.0, 1.0]
+	} else if (max - min).abs() < ::std::f64::EPSILON {
+		[min, min + 1.0]
+	} else {
+		[min, max]
+	}
+}
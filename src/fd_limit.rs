@@ -0,0 +1,65 @@
+use libc::{self, rlimit, RLIMIT_NOFILE};
+
+/// Raises the soft limit on open file descriptors towards the hard limit,
+/// so that running several nodes (plus their RPC sockets) concurrently
+/// doesn't quickly exhaust the default per-process cap.
+///
+/// On Darwin, the kernel additionally caps `RLIMIT_NOFILE` at
+/// `kern.maxfilesperproc`, so the raised value is clamped to that ceiling.
+/// Returns the new soft limit, or `None` if it could not be read/raised.
+pub fn raise_fd_limit() -> Option<u64> {
+	unsafe {
+		let mut limits = rlimit { rlim_cur: 0, rlim_max: 0 };
+		if libc::getrlimit(RLIMIT_NOFILE, &mut limits) != 0 {
+			return None;
+		}
+
+		let mut target = limits.rlim_max;
+
+		#[cfg(target_os = "macos")]
+		{
+			target = darwin_max_files_per_proc().map_or(target, |ceiling| target.min(ceiling));
+		}
+
+		if target <= limits.rlim_cur {
+			return Some(limits.rlim_cur);
+		}
+
+		limits.rlim_cur = target;
+		if libc::setrlimit(RLIMIT_NOFILE, &limits) != 0 {
+			return None;
+		}
+
+		Some(limits.rlim_cur)
+	}
+}
+
+/// Reads the `kern.maxfilesperproc` sysctl, which is the real ceiling
+/// `setrlimit(RLIMIT_NOFILE, ...)` is allowed to reach on Darwin (the
+/// `OPEN_MAX` constant in `<sys/syslimits.h>` is only a much lower default).
+#[cfg(target_os = "macos")]
+fn darwin_max_files_per_proc() -> Option<u64> {
+	use std::ffi::CString;
+	use std::mem;
+	use std::os::raw::c_void;
+
+	let name = CString::new("kern.maxfilesperproc").ok()?;
+	let mut value: libc::c_int = 0;
+	let mut size = mem::size_of::<libc::c_int>();
+
+	let result = unsafe {
+		libc::sysctlbyname(
+			name.as_ptr(),
+			&mut value as *mut _ as *mut c_void,
+			&mut size,
+			::std::ptr::null_mut(),
+			0,
+		)
+	};
+
+	if result == 0 {
+		Some(value as u64)
+	} else {
+		None
+	}
+}
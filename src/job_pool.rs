@@ -0,0 +1,45 @@
+use std::sync::{Arc, Condvar, Mutex};
+
+/// A simple counting semaphore used to bound how many `Runner`s are
+/// spawned at once, so we don't launch more nodes (and RPC sockets)
+/// than the caller asked for.
+#[derive(Clone)]
+pub struct JobPool {
+	state: Arc<(Mutex<usize>, Condvar)>,
+}
+
+pub struct JobToken {
+	pool: JobPool,
+}
+
+impl JobPool {
+	pub fn new(size: usize) -> Self {
+		JobPool { state: Arc::new((Mutex::new(size), Condvar::new())) }
+	}
+
+	/// Blocks until a slot is available, then returns a token that
+	/// releases the slot back to the pool when dropped.
+	pub fn acquire(&self) -> JobToken {
+		let (lock, cvar) = &*self.state;
+		let mut available = lock.lock().unwrap();
+		while *available == 0 {
+			available = cvar.wait(available).unwrap();
+		}
+		*available -= 1;
+
+		JobToken { pool: self.clone() }
+	}
+
+	fn release(&self) {
+		let (lock, cvar) = &*self.state;
+		let mut available = lock.lock().unwrap();
+		*available += 1;
+		cvar.notify_one();
+	}
+}
+
+impl Drop for JobToken {
+	fn drop(&mut self) {
+		self.pool.release();
+	}
+}
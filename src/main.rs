@@ -11,14 +11,29 @@ extern crate statrs;
 extern crate tempdir;
 extern crate gnuplot;
 extern crate regex;
+extern crate toml;
+#[macro_use]
+extern crate serde_derive;
+extern crate crossterm;
+extern crate tui;
+#[macro_use]
+extern crate serde_json;
+extern crate zstd;
 
+mod byte_size;
 mod child_guard;
+mod config;
+mod dashboard;
+mod fd_limit;
+mod job_pool;
+mod node_adapter;
 mod plotter;
 mod runner;
 
 use std::fs;
 use std::path::PathBuf;
 use std::io::{Error, ErrorKind};
+use std::thread;
 use std::time::Instant;
 
 use chrono::prelude::*;
@@ -26,11 +41,16 @@ use clap::{Arg, App};
 use console::style;
 use indicatif::HumanDuration;
 
+use self::config::Config;
+use self::fd_limit::raise_fd_limit;
+use self::job_pool::JobPool;
+use self::plotter::Plotter;
 use self::runner::Runner;
 
-const NUM_RUNS: usize = 3;
-
-fn run(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf) -> Result<(), Error> {
+/// Drives a single node through `config.num_runs` start/collect/stop cycles and
+/// leaves the collected series on the returned `Runner` for the caller
+/// to analyse/plot.
+fn run_single(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf, config: Config) -> Result<Runner, Error> {
 	if !fs::metadata(&bin_path)?.is_file() {
 		return Err(Error::new(ErrorKind::Other, "The given binary path is not a file."));
 	}
@@ -38,16 +58,14 @@ fn run(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf)
 		return Err(Error::new(ErrorKind::Other, "The given data path is not a directory."));
 	}
 
-	let now = Local::now();
-	let output_path = output_path.join(format!("{}_{}", name, now.format("%Y-%m-%dT%H:%M:%S").to_string()));
 	fs::create_dir_all(&output_path)?;
 
-    let started = Instant::now();
-	let mut runner = Runner::new(bin_path, data_path, name.clone(), output_path)?;
+	let num_runs = config.num_runs;
+	let mut runner = Runner::new(bin_path, data_path, name.clone(), output_path, config)?;
 
 	println!("Running metrics for {}\n", name);
 
-	for run_idx in 0..NUM_RUNS {
+	for run_idx in 0..num_runs {
 		println!(
 			"{} Starting the node for run #{}...",
 			style("[1/4]").bold().dim(), run_idx + 1
@@ -64,7 +82,7 @@ fn run(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf)
 			"{} Collecting data...",
 			style("[3/4]").bold().dim()
 		);
-		runner.collect_data()?;
+		let aborted = runner.collect_data()?;
 
 		println!(
 			"{} Stopping the node...",
@@ -73,10 +91,85 @@ fn run(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf)
 
 		runner.stop()?;
 		println!("");
+
+		if aborted {
+			println!("Aborted by the user, skipping the remaining runs.");
+			break;
+		}
 	}
 
+	Ok(runner)
+}
+
+fn run(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf, config: Config, compress: bool) -> Result<(), Error> {
+	let started = Instant::now();
+
+	let now = Local::now();
+	let output_path = output_path.join(format!("{}_{}", name, now.format("%Y-%m-%dT%H:%M:%S").to_string()));
+
+	let runner = run_single(bin_path, data_path, name, output_path, config)?;
 	runner.analyse()?;
 	runner.plot()?;
+	runner.export(compress)?;
+
+    println!("✨ Done in {}", HumanDuration(started.elapsed()));
+	Ok(())
+}
+
+/// Runs several binaries concurrently (bounded by `max_concurrent`), each
+/// on its own ports and temp data dir, then overlays their series on the
+/// same axes so different client versions can be compared directly.
+fn run_concurrent(bin_paths: Vec<String>, data_path: PathBuf, name: String, output_path: PathBuf, config: Config, max_concurrent: usize, compress: bool) -> Result<(), Error> {
+	let started = Instant::now();
+
+	if let Some(limit) = raise_fd_limit() {
+		println!("The open file descriptors limit is now {}.", limit);
+	}
+
+	let now = Local::now();
+	let output_path = output_path.join(format!("{}_{}", name, now.format("%Y-%m-%dT%H:%M:%S").to_string()));
+	fs::create_dir_all(&output_path)?;
+
+	let pool = JobPool::new(max_concurrent);
+	let handles: Vec<_> = bin_paths.into_iter().enumerate().map(|(index, bin_path)| {
+		let data_path = data_path.clone();
+		let output_path = output_path.join(format!("bin-{}", index + 1));
+		let name = format!("{}-{}", name, index + 1);
+		let config = config.clone();
+		let pool = pool.clone();
+
+		thread::spawn(move || {
+			let _token = pool.acquire();
+			run_single(bin_path, data_path, name, output_path, config)
+		})
+	}).collect();
+
+	let mut runners = Vec::new();
+	for handle in handles {
+		let runner = handle.join()
+			.map_err(|_| Error::new(ErrorKind::Other, "A run panicked."))??;
+		runner.analyse()?;
+		runner.plot()?;
+		runner.export(compress)?;
+		runners.push(runner);
+	}
+
+	if config.num_runs > 1 {
+		println!(
+			"Note: the overlay chart below only plots run #1 of each binary; see each binary's output folder for all {} runs.",
+			config.num_runs
+		);
+	}
+
+	let labels: Vec<String> = runners.iter().map(|runner| String::from(runner.node_version())).collect();
+	let block_heights: Vec<_> = runners.iter().filter_map(|runner| runner.block_heights().get(0).cloned()).collect();
+	let block_speeds: Vec<_> = runners.iter().filter_map(|runner| runner.block_speeds().get(0).cloned()).collect();
+	let peer_counts: Vec<_> = runners.iter().filter_map(|runner| runner.peer_counts().get(0).cloned()).collect();
+
+	let plotter = Plotter::with_labels(name, output_path, labels);
+	plotter.block_height(&block_heights);
+	plotter.block_speeds(&block_speeds);
+	plotter.peer_count(&peer_counts);
 
     println!("✨ Done in {}", HumanDuration(started.elapsed()));
 	Ok(())
@@ -91,8 +184,15 @@ fn main() {
 			.short("b")
 			.long("bin")
 			.value_name("BINARY")
-			.help("The binary of the ETH-node to run.")
+			.help("The binary of the ETH-node to run. Can be given multiple times to run several nodes concurrently and compare them.")
 			.required(true)
+			.multiple(true)
+			.number_of_values(1)
+			.takes_value(true))
+		.arg(Arg::with_name("concurrency")
+			.long("concurrency")
+			.value_name("COUNT")
+			.help("Maximum number of nodes to run at once when --bin is given multiple times (defaults to the number of binaries).")
 			.takes_value(true))
 		.arg(Arg::with_name("data")
 			.short("d")
@@ -115,10 +215,42 @@ fn main() {
 			.help("The folder where the outputs go.")
 			.required(true)
 			.takes_value(true))
+		.arg(Arg::with_name("config")
+			.short("c")
+			.long("config")
+			.value_name("FILE")
+			.help("The path of the TOML config file to use (defaults to the XDG config location).")
+			.takes_value(true))
+		.arg(Arg::with_name("chain")
+			.long("chain")
+			.value_name("CHAIN")
+			.help("Overrides the chain name from the config.")
+			.takes_value(true))
+		.arg(Arg::with_name("min-peers")
+			.long("min-peers")
+			.value_name("COUNT")
+			.help("Overrides the minimum peers count from the config.")
+			.takes_value(true))
+		.arg(Arg::with_name("num-runs")
+			.long("num-runs")
+			.value_name("COUNT")
+			.help("Overrides the number of runs from the config.")
+			.takes_value(true))
+		.arg(Arg::with_name("tui")
+			.long("tui")
+			.help("Show a live full-screen dashboard while collecting data instead of a progress bar."))
+		.arg(Arg::with_name("compress")
+			.long("compress")
+			.help("Compress the exported CSV/JSON time series with zstd."))
+		.arg(Arg::with_name("client")
+			.long("client")
+			.value_name("CLIENT")
+			.help("The node client to drive (\"parity\" or \"geth\"). Auto-detected from --version if omitted.")
+			.possible_values(&["parity", "geth"])
+			.takes_value(true))
         .get_matches();
 
-    let bin_path = matches.value_of("binary").unwrap();
-	let bin_path = String::from(bin_path);
+	let bin_paths: Vec<String> = matches.values_of("binary").unwrap().map(String::from).collect();
 
     let data_path = matches.value_of("data").unwrap();
 	let data_path = PathBuf::from(data_path).join("chains");
@@ -129,7 +261,71 @@ fn main() {
 	let output_path = matches.value_of("output").unwrap();
 	let output_path = PathBuf::from(output_path);
 
-    if let Err(error) = run(bin_path, data_path, name, output_path) {
+	let config_path = matches.value_of("config").map(PathBuf::from);
+	let mut config = match Config::load(config_path.as_ref().map(|p| p.as_path())) {
+		Ok(config) => config,
+		Err(error) => {
+			println!("{}{}", style("error: ").bold().red(), error);
+			::std::process::exit(1);
+		}
+	};
+
+	if let Some(chain) = matches.value_of("chain") {
+		config.chain = String::from(chain);
+	}
+	if let Some(min_peers) = matches.value_of("min-peers") {
+		match min_peers.parse() {
+			Ok(min_peers) => config.min_peers = min_peers,
+			Err(_) => {
+				println!("{}{}", style("error: ").bold().red(), "The given min-peers is not a valid number.");
+				::std::process::exit(1);
+			}
+		}
+	}
+	if let Some(num_runs) = matches.value_of("num-runs") {
+		match num_runs.parse() {
+			Ok(num_runs) => config.num_runs = num_runs,
+			Err(_) => {
+				println!("{}{}", style("error: ").bold().red(), "The given num-runs is not a valid number.");
+				::std::process::exit(1);
+			}
+		}
+	}
+	if matches.is_present("tui") {
+		config.tui = true;
+	}
+	if let Some(client) = matches.value_of("client") {
+		config.client = Some(String::from(client));
+	}
+	let compress = matches.is_present("compress");
+
+	if config.tui && bin_paths.len() > 1 {
+		println!(
+			"{}{}",
+			style("error: ").bold().red(),
+			"--tui is not supported together with several --bin (the dashboards would fight over the same terminal)."
+		);
+		::std::process::exit(1);
+	}
+
+	let result = if bin_paths.len() > 1 {
+		let max_concurrent = match matches.value_of("concurrency") {
+			Some(concurrency) => match concurrency.parse() {
+				Ok(concurrency) => concurrency,
+				Err(_) => {
+					println!("{}{}", style("error: ").bold().red(), "The given concurrency is not a valid number.");
+					::std::process::exit(1);
+				}
+			},
+			None => bin_paths.len(),
+		};
+
+		run_concurrent(bin_paths, data_path, name, output_path, config, max_concurrent, compress)
+	} else {
+		run(bin_paths.into_iter().next().unwrap(), data_path, name, output_path, config, compress)
+	};
+
+    if let Err(error) = result {
         println!("{}{}", style("error: ").bold().red(), error);
         ::std::process::exit(1);
     }
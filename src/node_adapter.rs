@@ -0,0 +1,101 @@
+use std::io::{Error, ErrorKind};
+use std::process::Command;
+
+use regex::Regex;
+use web3::{futures::Future, Web3, transports::Http as HttpTransport};
+
+use config::Config;
+
+/// Client-specific command-line flags, version parsing and readiness
+/// probing, so `Runner` stays agnostic of which ETH client it's driving.
+pub trait NodeAdapter: Send {
+	/// Builds the command used to spawn the node, given its data
+	/// directory and the p2p/RPC ports it should bind to.
+	fn build_command(&self, bin_path: &str, data_dir: &str, p2p_port: u16, rpc_port: u16, config: &Config) -> Command;
+
+	/// Extracts the version string out of the binary's `--version` output.
+	fn parse_version(&self, version_output: &str) -> Result<String, Error>;
+
+	/// Returns `true` once the node is ready to be queried over RPC.
+	fn readiness_probe(&self, web3: &Web3<HttpTransport>) -> bool {
+		web3.eth().block_number().wait().is_ok()
+	}
+}
+
+pub struct ParityAdapter;
+
+impl NodeAdapter for ParityAdapter {
+	fn build_command(&self, bin_path: &str, data_dir: &str, p2p_port: u16, rpc_port: u16, config: &Config) -> Command {
+		let mut command = Command::new(bin_path);
+		command
+			.arg("-d").arg(data_dir)
+			.arg("--chain").arg(&config.chain)
+			.arg("--min-peers").arg(config.min_peers.to_string())
+			.arg("--port").arg(p2p_port.to_string())
+			.arg("--jsonrpc-port").arg(rpc_port.to_string())
+			.arg("--no-warp")
+			.arg("--no-ws")
+			.arg("--no-ipc")
+			.arg("--no-secretstore")
+			.args(&config.extra_args);
+
+		command
+	}
+
+	fn parse_version(&self, version_output: &str) -> Result<String, Error> {
+		let re = Regex::new(r"version (?P<version>[^\s]+)").unwrap();
+		match re.captures(version_output) {
+			Some(ref captures) => Ok(String::from(&captures["version"])),
+			None => Err(Error::new(ErrorKind::Other, "Could not find version of the binary.")),
+		}
+	}
+}
+
+pub struct GethAdapter;
+
+impl NodeAdapter for GethAdapter {
+	fn build_command(&self, bin_path: &str, data_dir: &str, p2p_port: u16, rpc_port: u16, config: &Config) -> Command {
+		let mut command = Command::new(bin_path);
+		command
+			.arg("--datadir").arg(data_dir)
+			.arg("--networkid").arg(network_id(&config.chain).to_string())
+			.arg("--maxpeers").arg(config.min_peers.to_string())
+			.arg("--port").arg(p2p_port.to_string())
+			.arg("--http")
+			.arg("--http.port").arg(rpc_port.to_string())
+			.arg("--nat").arg("none")
+			.arg("--ipcdisable")
+			.args(&config.extra_args);
+
+		command
+	}
+
+	fn parse_version(&self, version_output: &str) -> Result<String, Error> {
+		let re = Regex::new(r"Version: (?P<version>[^\s]+)").unwrap();
+		match re.captures(version_output) {
+			Some(ref captures) => Ok(String::from(&captures["version"])),
+			None => Err(Error::new(ErrorKind::Other, "Could not find version of the binary.")),
+		}
+	}
+}
+
+fn network_id(chain: &str) -> u32 {
+	match chain {
+		"foundation" | "mainnet" => 1,
+		"ropsten" => 3,
+		"rinkeby" => 4,
+		"goerli" => 5,
+		_ => 1,
+	}
+}
+
+/// Picks an adapter by name (`--client parity`/`--client geth`), falling
+/// back to auto-detecting it from the binary's `--version` output.
+pub fn adapter_for(client: Option<&str>, version_output: &str) -> Box<dyn NodeAdapter> {
+	match client {
+		Some("geth") => Box::new(GethAdapter),
+		Some("parity") => Box::new(ParityAdapter),
+		_ if version_output.to_lowercase().contains("geth") => Box::new(GethAdapter),
+		_ => Box::new(ParityAdapter),
+	}
+}
@@ -22,12 +22,30 @@ struct PlotParams {
 pub struct Plotter {
 	name: String,
 	output_path: PathBuf,
+	labels: Vec<String>,
 }
 
 impl Plotter {
 	pub fn new(name: String, output_path: PathBuf) -> Self {
 		Plotter {
 			name, output_path,
+			labels: Vec::new(),
+		}
+	}
+
+	/// Like `new`, but captions each line with `labels[index]` instead of
+	/// the default `Run #N`. Used to overlay several nodes' series (e.g.
+	/// when comparing client versions) on the same axes.
+	pub fn with_labels(name: String, output_path: PathBuf, labels: Vec<String>) -> Self {
+		Plotter {
+			name, output_path, labels,
+		}
+	}
+
+	fn caption(&self, index: usize) -> String {
+		match self.labels.get(index) {
+			Some(label) => label.clone(),
+			None => format!("Run #{}", index + 1),
 		}
 	}
 
@@ -67,6 +85,42 @@ impl Plotter {
 		self.plot(params, lines);
 	}
 
+	pub fn sync_gap(&self, lines: &Vec<Line>) {
+		let params = PlotParams {
+			filepath: String::from("sync_gaps.png"),
+			title: String::from("Sync gap"),
+			y_label: String::from("Blocks behind"),
+			y_min: 0.0,
+			y_max: 1_000.0,
+		};
+
+		self.plot(params, lines);
+	}
+
+	pub fn gas_usage(&self, lines: &Vec<Line>) {
+		let params = PlotParams {
+			filepath: String::from("gas_usage.png"),
+			title: String::from("Gas usage"),
+			y_label: String::from("Gas used / limit"),
+			y_min: 0.0,
+			y_max: 1.0,
+		};
+
+		self.plot(params, lines);
+	}
+
+	pub fn txpool_depth(&self, lines: &Vec<Line>) {
+		let params = PlotParams {
+			filepath: String::from("txpool_depths.png"),
+			title: String::from("Txpool depth"),
+			y_label: String::from("Pending + queued transactions"),
+			y_min: 0.0,
+			y_max: 5_000.0,
+		};
+
+		self.plot(params, lines);
+	}
+
 	fn plot(&self, params: PlotParams, lines: &Vec<Line>) {
 		let mut fg = Figure::new();
 
@@ -81,12 +135,10 @@ impl Plotter {
 				.set_y_range(Fix(params.y_min), Fix(params.y_max))
 				.set_y_ticks(Some((Auto, 1)), &[Format("%'.0f")], &[]);
 
-			let mut index = 1;
-			for (times, data) in lines {
-				let caption = format!("Run #{}", index);
-				let color = COLORS[(index - 1) % COLORS.len()];
+			for (index, (times, data)) in lines.iter().enumerate() {
+				let caption = self.caption(index);
+				let color = COLORS[index % COLORS.len()];
 				fg_2d.lines(times, data, &[Caption(&caption), LineWidth(1.5), Color(color)]);
-				index += 1;
 			}
 		}
 
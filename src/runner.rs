@@ -12,20 +12,20 @@ use rand::{thread_rng, Rng};
 use rand::distributions::Uniform;
 use fs_extra::dir::{self, CopyOptions};
 use indicatif::{ProgressBar, ProgressStyle};
-use regex::Regex;
 use separator::Separatable;
 use statrs::statistics::{Min, Max, Mean, Variance};
 use tempdir::TempDir;
-use web3::{futures::Future, Web3, transports::Http as HttpTransport, transports::EventLoopHandle};
+use web3::{futures::Future, Transport, Web3, transports::Http as HttpTransport, transports::EventLoopHandle};
+use web3::types::{BlockId, BlockNumber, SyncState};
 
+use byte_size::human_bytes;
 use child_guard::ChildGuard;
+use config::Config;
+use dashboard::Dashboard;
+use node_adapter::{adapter_for, NodeAdapter};
 use plotter::{Plotter, Line};
 
-const ANALYSIS_TIME_SKIP: Duration = Duration::from_secs(60 * 5);
 const BLOCK_SPEEDS_AVERAGE_DURATION: Duration = Duration::from_secs(10);
-const DATA_COLLECTION_DURATION: Duration = Duration::from_secs(60 * 10);
-const DATA_COLLECTION_INTERVAL: Duration = Duration::from_millis(500);
-const MIN_PEERS: u32 = 75;
 
 fn duration_as_f64(duration: Duration) -> f64 {
     duration.as_secs() as f64 + duration.subsec_millis() as f64 / 1_000.0
@@ -35,20 +35,68 @@ fn duration_to_ms(duration: Duration) -> u64 {
 	duration.as_secs() * 1_000 + duration.subsec_millis() as u64
 }
 
-fn get_available_ports() -> Vec<u16> {
+/// Reserves ports by holding their listeners bound, rather than just
+/// checking availability and releasing it: two runners started concurrently
+/// each hold their own reservation, so the OS refuses a second bind on the
+/// same port instead of letting both pick it and race to actually claim it.
+/// Callers should only drop the returned listeners right before spawning
+/// the node that will bind these ports.
+fn reserve_ports() -> Vec<TcpListener> {
 	let mut rng = thread_rng();
 
 	rng.sample_iter(&Uniform::new_inclusive(8_000, 9_000))
-        .filter(|port| port_is_available(*port))
+		.filter_map(|port| TcpListener::bind(("127.0.0.1", port)).ok())
 		.take(2)
 		.collect()
 }
 
-fn port_is_available(port: u16) -> bool {
-    match TcpListener::bind(("127.0.0.1", port)) {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+/// Whether a metric has at least one sample across all runs. Clients that
+/// reject the underlying RPC call leave every `Line` empty.
+fn has_data(lines: &Vec<Line>) -> bool {
+	lines.iter().any(|(times, _)| !times.is_empty())
+}
+
+/// Appends min/max/mean/std-dev for a metric that may not be supported by
+/// every client: runs where nothing was sampled (an empty `Line`) are
+/// skipped instead of panicking, and the whole section is omitted if no
+/// run has any data at all.
+fn analyse_optional_metric(result: &mut String, label: &str, lines: &Vec<Line>, skip_index: usize) {
+	if !lines.iter().any(|(_times, values)| !values.is_empty()) {
+		return;
+	}
+
+	for (index, (_times, values)) in lines.iter().enumerate() {
+		if values.is_empty() {
+			continue;
+		}
+
+		let skip = if values.len() > skip_index { skip_index } else { 0 };
+		let min = values[skip..].min();
+		let max = values[skip..].max();
+		let mean = values[skip..].mean();
+		let std_dev = values[skip..].std_dev();
+
+		result.push_str(&format!(
+			"  - [{}] Run #{}: min={:.3} ; max={:.3} ; mean={:.3} ; std_dev={:.3}\n",
+			label, index + 1, min, max, mean, std_dev));
+	}
+	result.push_str("\n");
+}
+
+/// `txpool_status` isn't exposed as a typed namespace by the `web3` crate,
+/// so it's issued as a raw RPC call and the hex-encoded counts are summed.
+fn poll_txpool_depth(web3: &Web3<HttpTransport>) -> Result<f64, Error> {
+	let status = web3.transport().execute("txpool_status", Vec::new()).wait()
+		.map_err(|e| Error::new(ErrorKind::Other, format!("{}", e)))?;
+
+	let count = |field: &str| -> u64 {
+		status.get(field)
+			.and_then(|value| value.as_str())
+			.and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+			.unwrap_or(0)
+	};
+
+	Ok((count("pending") + count("queued")) as f64)
 }
 
 pub struct Runner {
@@ -57,18 +105,28 @@ pub struct Runner {
 	output_path: PathBuf,
 	name: String,
 	version: String,
+	config: Config,
+	adapter: Box<dyn NodeAdapter>,
 	tmp_dir: Option<TempDir>,
 	child: Option<ChildGuard>,
 	web3: Option<(Web3<HttpTransport>, EventLoopHandle)>,
 	block_heights: Vec<Line>,
 	block_speeds: Vec<Line>,
 	peer_counts: Vec<Line>,
+	sync_gaps: Vec<Line>,
+	gas_usages: Vec<Line>,
+	txpool_depths: Vec<Line>,
+	supports_syncing: bool,
+	supports_gas_usage: bool,
+	supports_txpool: bool,
 }
 
 impl Runner {
-	/// Creates a new runner with the given binary path
-	pub fn new(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf) -> Result<Self, Error> {
-		let version = Runner::version(&bin_path)?;
+	/// Creates a new runner with the given binary path and configuration
+	pub fn new(bin_path: String, data_path: PathBuf, name: String, output_path: PathBuf, config: Config) -> Result<Self, Error> {
+		let version_output = Runner::version_output(&bin_path)?;
+		let adapter = adapter_for(config.client.as_ref().map(|client| client.as_str()), &version_output);
+		let version = adapter.parse_version(&version_output)?;
 
 		Ok(Runner {
 			bin_path,
@@ -76,36 +134,37 @@ impl Runner {
 			output_path,
 			name,
 			version,
+			config,
+			adapter,
 			tmp_dir: None,
 			child: None,
 			web3: None,
 			block_heights: Vec::new(),
 			block_speeds: Vec::new(),
 			peer_counts: Vec::new(),
+			sync_gaps: Vec::new(),
+			gas_usages: Vec::new(),
+			txpool_depths: Vec::new(),
+			supports_syncing: true,
+			supports_gas_usage: true,
+			supports_txpool: true,
 		})
 	}
 
-	/// Get the version of the given binary
-	fn version(bin_path: &String) -> Result<String, Error> {
+	/// Run the binary with `--version` and return its raw stdout, used to
+	/// pick a `NodeAdapter` and to extract the version string.
+	fn version_output(bin_path: &String) -> Result<String, Error> {
 		let output = Command::new(bin_path)
 			.arg("--version")
 			.output()?;
 
-		let re = Regex::new(r"version (?P<version>[^\s]+)").unwrap();
-		let stdout = String::from_utf8_lossy(&output.stdout);
-		let captures = re.captures(&stdout);
-		let version = match captures {
-			Some(ref captures) => &captures["version"],
-			_ => return Err(Error::new(ErrorKind::Other, "Could not find version of the binary.")),
-		};
-
-		Ok(String::from(version))
+		Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 	}
 
 	/// Start the node with the pre-defined configuration
 	pub fn start(&mut self) -> Result<(), Error> {
 		let tmp_dir = TempDir::new("eth-metrics")?;
-		let tmp_data_dir_path = tmp_dir.path().join("parity-data");
+		let tmp_data_dir_path = tmp_dir.path().join("node-data");
 		let tmp_data_dir = match tmp_data_dir_path.to_str() {
 			Some(tmp_data_dir) => tmp_data_dir,
 			None => return Err(Error::new(ErrorKind::Other, "Could not find the node's data directory.")),
@@ -118,22 +177,19 @@ impl Runner {
 			_ => (),
 		}
 
-		let ports = get_available_ports();
-		if ports.len() < 2 {
+		let listeners = reserve_ports();
+		if listeners.len() < 2 {
 			return Err(Error::new(ErrorKind::Other, "Could not find any available port."));
 		}
-		let port = ports[0];
-		let rpc_port = ports[1];
-		let child = Command::new(&self.bin_path)
-			.arg("-d").arg(tmp_data_dir)
-			.arg("--chain").arg("foundation")
-			.arg("--min-peers").arg(MIN_PEERS.to_string())
-			.arg("--port").arg(port.to_string())
-			.arg("--jsonrpc-port").arg(rpc_port.to_string())
-			.arg("--no-warp")
-			.arg("--no-ws")
-			.arg("--no-ipc")
-			.arg("--no-secretstore")
+		let port = listeners[0].local_addr()?.port();
+		let rpc_port = listeners[1].local_addr()?.port();
+
+		// Only release the reservation right before spawning, narrowing the
+		// window in which another runner could grab the same port (it stays
+		// open until the node has bound it).
+		drop(listeners);
+
+		let child = self.adapter.build_command(&self.bin_path, tmp_data_dir, port, rpc_port, &self.config)
 			.stderr(Stdio::piped())
 			.stdout(Stdio::piped())
 			.spawn()?;
@@ -192,31 +248,36 @@ impl Runner {
 			if timedout.load(Ordering::SeqCst) {
 				return Err(Error::new(ErrorKind::Other, "Node was node ready even after 5s."));
 			}
-            match web3.eth().block_number().wait() {
-                Ok(_) => {
-                    break;
-                },
-                Err(_e) => {
-					// println!("Error: {}", e);
-                    thread::sleep(Duration::from_millis(500));
-                }
+            if self.adapter.readiness_probe(web3) {
+                break;
             }
+            thread::sleep(Duration::from_millis(500));
         }
 
 		Ok(())
 	}
 
-	/// Collect some data for some time
-	pub fn collect_data(&mut self) -> Result<(), Error> {
+	/// Collect some data for some time. Returns `true` if the user aborted
+	/// the collection early (`q`/Ctrl-C on the TUI dashboard), in which
+	/// case the caller should stop the whole run rather than starting
+	/// another one.
+	pub fn collect_data(&mut self) -> Result<bool, Error> {
 		let web3 = match self.web3 {
 			Some((ref web3, _)) => web3,
 			None => return Err(Error::new(ErrorKind::Other, "The Runner has not been started yet.")),
 		};
 
-        let pb = ProgressBar::new(duration_to_ms(DATA_COLLECTION_DURATION));
-        let spinner_style = ProgressStyle::default_bar()
-            .template("{spinner:.green} {bar:40.cyan/blue} {msg} ({eta})");
-        pb.set_style(spinner_style);
+		let data_collection_duration = self.config.data_collection_duration();
+		let data_collection_interval = self.config.data_collection_interval();
+
+		let mut pb = if self.config.tui { None } else {
+			let pb = ProgressBar::new(duration_to_ms(data_collection_duration));
+			let spinner_style = ProgressStyle::default_bar()
+				.template("{spinner:.green} {bar:40.cyan/blue} {msg} ({eta})");
+			pb.set_style(spinner_style);
+			Some(pb)
+		};
+		let mut dashboard = if self.config.tui { Some(Dashboard::new(self.config.min_peers)?) } else { None };
 
         let start = Instant::now();
 		let mut elapsed = Duration::new(0, 0);
@@ -225,7 +286,16 @@ impl Runner {
 		let mut block_heights = Vec::new();
 		let mut peer_counts = Vec::new();
 
-        while elapsed < DATA_COLLECTION_DURATION {
+		let mut sync_times = Vec::new();
+		let mut sync_gaps = Vec::new();
+		let mut gas_times = Vec::new();
+		let mut gas_usages = Vec::new();
+		let mut txpool_times = Vec::new();
+		let mut txpool_depths = Vec::new();
+
+        let mut aborted = false;
+
+        while elapsed < data_collection_duration {
 			match self.child {
 				Some(ref mut child) => child.check()?,
 				_ => (),
@@ -239,18 +309,70 @@ impl Runner {
 				Err(_) => return Err(Error::new(ErrorKind::Other, "Could not fetch peer count.")),
 			};
 
-			times.push(duration_as_f64(elapsed));
-			block_heights.push(block_number.as_u32() as f64);
+			let time = duration_as_f64(elapsed);
+			let block_height = block_number.as_u32() as f64;
+			let prev_block_height = block_heights.last().cloned().unwrap_or(block_height);
+			let instant_speed = (block_height - prev_block_height) / duration_as_f64(data_collection_interval);
+
+			times.push(time);
+			block_heights.push(block_height);
 			peer_counts.push(peer_count.as_u32() as f64);
 
-            pb.set_position(duration_to_ms(elapsed));
-            pb.set_message(format!(
-				"[#{} ; {:2}/{}]",
-				block_number.as_u64().separated_string(), peer_count,
-				MIN_PEERS
-			).as_str());
+			if self.supports_syncing {
+				match web3.eth().syncing().wait() {
+					Ok(SyncState::Syncing(info)) => {
+						let gap = info.highest_block.as_u64() as f64 - info.current_block.as_u64() as f64;
+						sync_times.push(time);
+						sync_gaps.push(gap.max(0.0));
+					},
+					Ok(SyncState::NotSyncing) => {
+						sync_times.push(time);
+						sync_gaps.push(0.0);
+					},
+					Err(_) => self.supports_syncing = false,
+				}
+			}
+
+			if self.supports_gas_usage {
+				match web3.eth().block(BlockId::Number(BlockNumber::Latest)).wait() {
+					Ok(Some(ref block)) if !block.gas_limit.is_zero() => {
+						gas_times.push(time);
+						gas_usages.push(block.gas_used.as_u64() as f64 / block.gas_limit.as_u64() as f64);
+					},
+					Ok(_) => (),
+					Err(_) => self.supports_gas_usage = false,
+				}
+			}
+
+			if self.supports_txpool {
+				match poll_txpool_depth(web3) {
+					Ok(depth) => {
+						txpool_times.push(time);
+						txpool_depths.push(depth);
+					},
+					Err(_) => self.supports_txpool = false,
+				}
+			}
 
-            thread::sleep(DATA_COLLECTION_INTERVAL);
+			if let Some(ref pb) = pb {
+				pb.set_position(duration_to_ms(elapsed));
+				pb.set_message(format!(
+					"[#{} ; {:2}/{}]",
+					block_number.as_u64().separated_string(), peer_count,
+					self.config.min_peers
+				).as_str());
+			}
+
+			if let Some(ref mut dashboard) = dashboard {
+				dashboard.push(time, block_height, instant_speed, peer_count.as_u64());
+				dashboard.draw()?;
+				if dashboard.should_quit()? {
+					aborted = true;
+					break;
+				}
+			}
+
+            thread::sleep(data_collection_interval);
 			elapsed = Instant::now().duration_since(start);
         }
 
@@ -260,7 +382,10 @@ impl Runner {
 			// is the duration of the collect)
 			let avg_secs = BLOCK_SPEEDS_AVERAGE_DURATION.as_secs() as f64;
 			let duration = times[times.len() - 1];
-			let avg_factor = (times.len() as f64 / duration * avg_secs) as usize;
+			// A large `data_collection_interval_ms` can make this compute to
+			// 0 (fewer than one sample per averaging window); clamp to 1 so
+			// the loop below never divides by zero.
+			let avg_factor = ((times.len() as f64 / duration * avg_secs) as usize).max(1);
 
 			let mut block_speeds = Vec::new();
 			let mut block_speeds_times = Vec::new();
@@ -286,8 +411,11 @@ impl Runner {
 		self.block_heights.push((times.clone(), block_heights));
 		self.block_speeds.push(block_speeds_line);
 		self.peer_counts.push((times.clone(), peer_counts));
+		self.sync_gaps.push((sync_times, sync_gaps));
+		self.gas_usages.push((gas_times, gas_usages));
+		self.txpool_depths.push((txpool_times, txpool_depths));
 
-		Ok(())
+		Ok(aborted)
 	}
 
 	pub fn analyse(&self) -> Result<(), Error> {
@@ -300,13 +428,19 @@ impl Runner {
 		result.push_str("Analysis results:\n");
 		result.push_str(&format!("  - Version: {}\n\n", self.version));
 
-		let skip_index = (duration_as_f64(ANALYSIS_TIME_SKIP) / duration_as_f64(DATA_COLLECTION_INTERVAL)) as usize;
+		let skip_index_by_interval = (duration_as_f64(self.config.analysis_time_skip()) / duration_as_f64(self.config.data_collection_interval())) as usize;
 		let mut index = 1;
 		for (_times, peer_count) in self.peer_counts.iter() {
-			let min = peer_count[skip_index..].min();
-			let max = peer_count[skip_index..].max();
-			let mean = peer_count[skip_index..].mean();
-			let std_dev = peer_count[skip_index..].std_dev();
+			if peer_count.is_empty() {
+				index += 1;
+				continue;
+			}
+
+			let skip = if peer_count.len() > skip_index_by_interval { skip_index_by_interval } else { 0 };
+			let min = peer_count[skip..].min();
+			let max = peer_count[skip..].max();
+			let mean = peer_count[skip..].mean();
+			let std_dev = peer_count[skip..].std_dev();
 
 			result.push_str(&format!(
 				"  - [Peer Count] Run #{}: min={:.0} ; max={:.0} ; mean={:.2} ; std_dev={:.2}\n",
@@ -316,12 +450,19 @@ impl Runner {
 		result.push_str("\n");
 
 		// Block speeds are averaged every BLOCK_SPEEDS_AVERAGE_DURATION second
-		let skip_index = (ANALYSIS_TIME_SKIP.as_secs() / BLOCK_SPEEDS_AVERAGE_DURATION.as_secs()) as usize;
+		let skip_index = (self.config.analysis_time_skip().as_secs() / BLOCK_SPEEDS_AVERAGE_DURATION.as_secs()) as usize;
 		let mut index = 1;
 		for (_times, block_speeds) in self.block_speeds.iter() {
-			let mean = block_speeds[skip_index..].mean();
-			let std_dev = block_speeds[skip_index..].std_dev();
-			let max = self.block_heights[index - 1].1[self.block_heights[index - 1].1.len() - 1];
+			let block_heights = &self.block_heights[index - 1].1;
+			if block_speeds.is_empty() || block_heights.is_empty() {
+				index += 1;
+				continue;
+			}
+
+			let skip = if block_speeds.len() > skip_index { skip_index } else { 0 };
+			let mean = block_speeds[skip..].mean();
+			let std_dev = block_speeds[skip..].std_dev();
+			let max = block_heights[block_heights.len() - 1];
 
 			result.push_str(&format!(
 				"  - [Block Height] Run #{}: max={:.0} ; mean_speed={:.2}bps ; std_dev={:.2}\n",
@@ -330,6 +471,10 @@ impl Runner {
 		}
 		result.push_str("\n");
 
+		analyse_optional_metric(&mut result, "Sync Gap", &self.sync_gaps, skip_index_by_interval);
+		analyse_optional_metric(&mut result, "Gas Usage", &self.gas_usages, skip_index_by_interval);
+		analyse_optional_metric(&mut result, "Txpool Depth", &self.txpool_depths, skip_index_by_interval);
+
 		let filepath = self.output_path.join("results.md");
 		let mut file = File::create(filepath)?;
 		write!(file, "{}", result);
@@ -350,8 +495,109 @@ impl Runner {
 		plotter.block_speeds(&self.block_speeds);
 		plotter.peer_count(&self.peer_counts);
 
+		if has_data(&self.sync_gaps) {
+			plotter.sync_gap(&self.sync_gaps);
+		}
+		if has_data(&self.gas_usages) {
+			plotter.gas_usage(&self.gas_usages);
+		}
+		if has_data(&self.txpool_depths) {
+			plotter.txpool_depth(&self.txpool_depths);
+		}
+
+		Ok(())
+	}
+
+	/// Export the raw per-run time series to `*.csv` (one file per metric,
+	/// `time,value,run_idx` columns) plus a combined `metrics.json`, so
+	/// collected runs can be re-plotted or diffed later without re-running
+	/// a full collection. With `compress`, both are streamed through zstd
+	/// and written as `.csv.zst`/`.json.zst` instead. Sync gap, gas usage
+	/// and txpool depth get no CSV when no run has any sample of them.
+	pub fn export(&self, compress: bool) -> Result<(), Error> {
+		if self.block_heights.len() == 0 {
+			return Err(Error::new(ErrorKind::Other, "No data have been collected."));
+		}
+
+		self.export_csv("block_heights.csv", &self.block_heights, compress)?;
+		self.export_csv("block_speeds.csv", &self.block_speeds, compress)?;
+		self.export_csv("peer_counts.csv", &self.peer_counts, compress)?;
+		if has_data(&self.sync_gaps) {
+			self.export_csv("sync_gaps.csv", &self.sync_gaps, compress)?;
+		}
+		if has_data(&self.gas_usages) {
+			self.export_csv("gas_usages.csv", &self.gas_usages, compress)?;
+		}
+		if has_data(&self.txpool_depths) {
+			self.export_csv("txpool_depths.csv", &self.txpool_depths, compress)?;
+		}
+		self.export_json(compress)?;
+
+		Ok(())
+	}
+
+	fn export_csv(&self, filename: &str, lines: &Vec<Line>, compress: bool) -> Result<(), Error> {
+		let mut csv = String::from("time,value,run_idx\n");
+		for (run_idx, (times, values)) in lines.iter().enumerate() {
+			for (time, value) in times.iter().zip(values.iter()) {
+				csv.push_str(&format!("{},{},{}\n", time, value, run_idx + 1));
+			}
+		}
+
+		self.write_export(filename, csv.into_bytes(), compress)
+	}
+
+	fn export_json(&self, compress: bool) -> Result<(), Error> {
+		let metrics = json!({
+			"name": self.name,
+			"version": self.version,
+			"block_heights": self.block_heights,
+			"block_speeds": self.block_speeds,
+			"peer_counts": self.peer_counts,
+			"sync_gaps": self.sync_gaps,
+			"gas_usages": self.gas_usages,
+			"txpool_depths": self.txpool_depths,
+		});
+
+		let contents = serde_json::to_string_pretty(&metrics)
+			.map_err(|e| Error::new(ErrorKind::Other, format!("Could not serialize metrics: {}", e)))?;
+
+		self.write_export("metrics.json", contents.into_bytes(), compress)
+	}
+
+	fn write_export(&self, filename: &str, contents: Vec<u8>, compress: bool) -> Result<(), Error> {
+		let (filename, contents) = if compress {
+			let compressed = zstd::encode_all(&contents[..], 0)
+				.map_err(|e| Error::new(ErrorKind::Other, format!("Could not compress {}: {}", filename, e)))?;
+			(format!("{}.zst", filename), compressed)
+		} else {
+			(String::from(filename), contents)
+		};
+
+		let filepath = self.output_path.join(&filename);
+		let mut file = File::create(&filepath)?;
+		file.write_all(&contents)?;
+
+		println!("Wrote {} ({})", filepath.display(), human_bytes(contents.len() as u64));
+
 		Ok(())
 	}
+
+	pub fn node_version(&self) -> &str {
+		&self.version
+	}
+
+	pub fn block_heights(&self) -> &Vec<Line> {
+		&self.block_heights
+	}
+
+	pub fn block_speeds(&self) -> &Vec<Line> {
+		&self.block_speeds
+	}
+
+	pub fn peer_counts(&self) -> &Vec<Line> {
+		&self.peer_counts
+	}
 }
 
 impl Drop for Runner {